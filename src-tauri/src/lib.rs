@@ -1,10 +1,369 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
-use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{Manager, WindowEvent, Emitter};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, WindowEvent, Emitter, Listener, Wry};
 use tauri_plugin_notification::NotificationExt;
-use std::time::Duration;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_updater::UpdaterExt;
+use std::time::{Duration, SystemTime};
 use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+// Default idle timeout before the window auto-locks. 0 disables the feature.
+const DEFAULT_AUTO_LOCK_MINUTES: u64 = 15;
+
+// Tracks activity for the idle auto-lock feature. Uses wall-clock time so
+// time spent suspended counts toward the idle timeout too.
+struct AutoLockState {
+    last_activity: Mutex<SystemTime>,
+    timeout_minutes: Mutex<u64>,
+}
+
+fn record_activity(app: &AppHandle) {
+    if let Some(auto_lock) = app.try_state::<AutoLockState>() {
+        *auto_lock.last_activity.lock().unwrap() = SystemTime::now();
+    }
+}
+
+fn lock_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    let _ = app.emit("lock", ());
+    let _ = refresh_tray(app, Some(false), None, None);
+    record_activity(app);
+}
+
+// Hides the window and emits `lock` if the idle timeout has elapsed, then
+// resets the activity timer so it doesn't refire every tick.
+fn check_auto_lock(app: &AppHandle, auto_lock: &AutoLockState) {
+    let timeout_minutes = *auto_lock.timeout_minutes.lock().unwrap();
+    if timeout_minutes == 0 {
+        return;
+    }
+
+    let idle = SystemTime::now()
+        .duration_since(*auto_lock.last_activity.lock().unwrap())
+        .unwrap_or_default();
+    if idle >= Duration::from_secs(timeout_minutes * 60) {
+        lock_main_window(app);
+    }
+}
+
+// How far into the future (in minutes) we scan for upcoming due action items.
+const DUE_LOOKAHEAD_MINUTES: i64 = 60;
+
+#[derive(sqlx::FromRow)]
+struct DueActionItem {
+    id: String,
+    title: String,
+    due_date: String,
+}
+
+async fn open_db_pool(app: &tauri::AppHandle) -> Result<sqlx::SqlitePool, sqlx::Error> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .expect("app data dir should be resolvable")
+        .join("engage360.db");
+
+    let options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true);
+
+    sqlx::sqlite::SqlitePoolOptions::new().connect_with(options).await
+}
+
+async fn fetch_due_action_items(pool: &sqlx::SqlitePool, lookahead_minutes: i64) -> Result<Vec<DueActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, DueActionItem>(
+        "SELECT id, title, due_date FROM action_items \
+         WHERE due_date IS NOT NULL \
+           AND datetime(due_date) >= datetime('now') \
+           AND datetime(due_date) <= datetime('now', ?)",
+    )
+    .bind(format!("+{} minutes", lookahead_minutes))
+    .fetch_all(pool)
+    .await
+}
+
+fn show_due_item_notification(app: &tauri::AppHandle, title: &str, due_date: &str) -> tauri::Result<()> {
+    let notification_title = "Action Item Due";
+    let notification_body = format!("\"{}\" is due {}", title, due_date);
+
+    app.notification()
+        .builder()
+        .title(notification_title)
+        .body(&notification_body)
+        .show()
+}
+
+// Queries the database for newly-due action items, notifies for any not
+// already seen, and keeps the tray's due-count in sync. Keys `notified` on
+// `(id, due_date)` so a rescheduled item can notify again.
+async fn check_due_action_items(
+    app: &tauri::AppHandle,
+    pool: &sqlx::SqlitePool,
+    notified: &Mutex<HashSet<(String, String)>>,
+) {
+    let items = match fetch_due_action_items(pool, DUE_LOOKAHEAD_MINUTES).await {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("Failed to query due action items: {}", e);
+            return;
+        }
+    };
+
+    let due_count = items.len() as u32;
+    {
+        let mut notified = notified.lock().unwrap();
+        for item in &items {
+            if notified.insert((item.id.clone(), item.due_date.clone())) {
+                if let Err(e) = show_due_item_notification(app, &item.title, &item.due_date) {
+                    eprintln!("Failed to show due-item notification: {}", e);
+                }
+            }
+        }
+    }
+
+    let _ = refresh_tray(app, None, Some(due_count), None);
+}
+
+// Shows the main window if it's hidden, hides it otherwise, and keeps the
+// tray in sync. Shared by the tray's left-click handler and the global
+// "toggle window" shortcut.
+fn toggle_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let now_visible = !window.is_visible().unwrap_or(false);
+        if now_visible {
+            let _ = window.show();
+            let _ = window.set_focus();
+        } else {
+            let _ = window.hide();
+        }
+        let _ = refresh_tray(app, Some(now_visible), None, None);
+    }
+}
+
+// A registered global shortcut: the accelerator string (for persistence and
+// display) alongside the parsed `Shortcut` actually handed to the plugin.
+struct ShortcutRegistry {
+    bindings: HashMap<String, (String, Shortcut)>,
+}
+
+fn default_shortcut_bindings() -> HashMap<String, String> {
+    HashMap::from([
+        ("toggle_window".to_string(), "CommandOrControl+Shift+E".to_string()),
+        ("new_action_item".to_string(), "CommandOrControl+Shift+N".to_string()),
+    ])
+}
+
+fn load_shortcut_bindings(app: &AppHandle) -> HashMap<String, String> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join("shortcuts.json"))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str::<HashMap<String, String>>(&contents).ok())
+        .unwrap_or_else(default_shortcut_bindings)
+}
+
+fn save_shortcut_bindings(app: &AppHandle, bindings: &HashMap<String, String>) -> std::io::Result<()> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    std::fs::create_dir_all(&dir)?;
+    let contents = serde_json::to_string_pretty(bindings).unwrap_or_default();
+    std::fs::write(dir.join("shortcuts.json"), contents)
+}
+
+// How many 60-second background-loop ticks to wait between update checks.
+const UPDATE_CHECK_TICK_INTERVAL: u32 = 30;
+
+#[derive(Clone, serde::Serialize)]
+struct UpdateInfo {
+    version: String,
+    notes: Option<String>,
+}
+
+// Holds the update returned by the last successful check, ready to install.
+struct UpdateState {
+    pending: Mutex<Option<tauri_plugin_updater::Update>>,
+}
+
+// Checks for an update; if one is found, emits `update-available`, stashes
+// it for `install_update`, and adds the tray's "Install Update…" item.
+async fn check_for_update_internal(app: &AppHandle) -> tauri_plugin_updater::Result<Option<UpdateInfo>> {
+    let Some(update) = app.updater()?.check().await? else {
+        return Ok(None);
+    };
+
+    let info = UpdateInfo {
+        version: update.version.clone(),
+        notes: update.body.clone(),
+    };
+
+    *app.state::<UpdateState>().pending.lock().unwrap() = Some(update);
+    let _ = app.emit("update-available", &info);
+    let _ = refresh_tray(app, None, None, Some(true));
+
+    Ok(Some(info))
+}
+
+// Downloads and installs the update stashed by `check_for_update_internal`,
+// then restarts the app. No-op if nothing is pending.
+async fn install_update_internal(app: &AppHandle) -> tauri_plugin_updater::Result<()> {
+    let update = app.state::<UpdateState>().pending.lock().unwrap().take();
+    let Some(update) = update else {
+        return Ok(());
+    };
+
+    update.download_and_install(|_chunk_len, _total| {}, || {}).await?;
+    app.restart();
+}
+
+// Dispatches a triggered global shortcut to the matching app action.
+fn handle_global_shortcut(app: &AppHandle, shortcut: &Shortcut) {
+    let registry = app.state::<Mutex<ShortcutRegistry>>();
+    let action = {
+        let registry = registry.lock().unwrap();
+        registry
+            .bindings
+            .iter()
+            .find(|(_, (_, bound))| bound == shortcut)
+            .map(|(action, _)| action.clone())
+    };
+
+    match action.as_deref() {
+        Some("toggle_window") => toggle_main_window(app),
+        Some("new_action_item") => {
+            let _ = app.emit("new-action-item", ());
+        }
+        _ => {}
+    }
+}
+
+// Tracks everything needed to keep the tray menu/tooltip in sync with app state.
+struct TrayState {
+    tray: TrayIcon<Wry>,
+    visible: bool,
+    due_count: u32,
+    update_available: bool,
+}
+
+fn build_tray_menu(app: &AppHandle, visible: bool, update_available: bool) -> tauri::Result<Menu<Wry>> {
+    let toggle_item = if visible {
+        MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?
+    } else {
+        MenuItem::with_id(app, "show", "Show", true, None::<&str>)?
+    };
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    if update_available {
+        let install_item = MenuItem::with_id(app, "install_update", "Install Update…", true, None::<&str>)?;
+        Menu::with_items(app, &[&toggle_item, &install_item, &quit_item])
+    } else {
+        Menu::with_items(app, &[&toggle_item, &quit_item])
+    }
+}
+
+fn tray_tooltip(due_count: u32) -> String {
+    match due_count {
+        0 => "Engage360".to_string(),
+        1 => "1 action item due".to_string(),
+        n => format!("{} action items due", n),
+    }
+}
+
+// Rebuilds the tray menu and tooltip from the latest known window visibility,
+// due-item count, and update availability. Pass `None` for whichever value
+// hasn't changed.
+fn refresh_tray(
+    app: &AppHandle,
+    visible: Option<bool>,
+    due_count: Option<u32>,
+    update_available: Option<bool>,
+) -> tauri::Result<()> {
+    let state = app.state::<Mutex<TrayState>>();
+    let mut tray_state = state.lock().unwrap();
+
+    if let Some(visible) = visible {
+        tray_state.visible = visible;
+    }
+    if let Some(due_count) = due_count {
+        tray_state.due_count = due_count;
+    }
+    if let Some(update_available) = update_available {
+        tray_state.update_available = update_available;
+    }
+
+    let menu = build_tray_menu(app, tray_state.visible, tray_state.update_available)?;
+    tray_state.tray.set_menu(Some(menu))?;
+    tray_state.tray.set_tooltip(Some(tray_tooltip(tray_state.due_count)))?;
+
+    Ok(())
+}
+
+// A single entry in the `run_command` allow-list: the exact executable name,
+// the argument prefixes it may be called with, and any args that are always
+// prepended regardless of what the frontend passes in.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct AllowedCommand {
+    name: String,
+    arg_prefixes: Vec<String>,
+    #[serde(default)]
+    fixed_args: Vec<String>,
+}
+
+struct CommandScope {
+    allowed: Vec<AllowedCommand>,
+}
+
+impl CommandScope {
+    fn find(&self, command: &str) -> Option<&AllowedCommand> {
+        self.allowed.iter().find(|c| c.name == command)
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", content = "message")]
+enum CommandError {
+    CommandNotAllowed(String),
+    ArgumentNotAllowed(String),
+    Io(String),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::CommandNotAllowed(command) => write!(f, "command '{}' is not in the allow-list", command),
+            CommandError::ArgumentNotAllowed(arg) => write!(f, "argument '{}' is not permitted for this command", arg),
+            CommandError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+// Built-in allow-list used when no `command_scope.json` exists yet. Empty
+// by default: running any command is an explicit opt-in via that file.
+fn default_command_scope() -> Vec<AllowedCommand> {
+    vec![]
+}
+
+fn load_command_scope(app: &tauri::AppHandle) -> CommandScope {
+    let allowed = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join("command_scope.json"))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str::<Vec<AllowedCommand>>(&contents).ok())
+        .unwrap_or_else(default_command_scope);
+
+    CommandScope { allowed }
+}
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -37,17 +396,7 @@ fn send_notification(app: tauri::AppHandle, title: String, body: String) -> Resu
 
 #[tauri::command]
 async fn notify_due_action_item(app: tauri::AppHandle, title: String, due_date: String) -> Result<(), String> {
-    let notification_title = "Action Item Due";
-    let notification_body = format!("\"{}\" is due {}", title, due_date);
-
-    app.notification()
-        .builder()
-        .title(notification_title)
-        .body(&notification_body)
-        .show()
-        .map_err(|e| e.to_string())?;
-
-    Ok(())
+    show_due_item_notification(&app, &title, &due_date).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -60,11 +409,37 @@ fn clear_due_item_notifications(app: tauri::AppHandle) -> Result<String, String>
 }
 
 #[tauri::command]
-fn run_command(command: String, args: Vec<String>) -> Result<serde_json::Value, String> {
+fn report_due_item_count(app: tauri::AppHandle, count: u32) -> Result<(), String> {
+    refresh_tray(&app, None, Some(count), None).map_err(|e| e.to_string())
+}
+
+// Validates `args` against `allowed`'s prefix list and prepends its fixed
+// args. Pulled out of `run_command` so it can be unit-tested directly.
+fn build_allowed_args(allowed: &AllowedCommand, args: Vec<String>) -> Result<Vec<String>, CommandError> {
+    for arg in &args {
+        if !allowed.arg_prefixes.iter().any(|prefix| arg.starts_with(prefix.as_str())) {
+            return Err(CommandError::ArgumentNotAllowed(arg.clone()));
+        }
+    }
+
+    let mut full_args = allowed.fixed_args.clone();
+    full_args.extend(args);
+    Ok(full_args)
+}
+
+#[tauri::command]
+fn run_command(app: tauri::AppHandle, command: String, args: Vec<String>) -> Result<serde_json::Value, CommandError> {
+    let scope = app.state::<CommandScope>();
+    let allowed = scope
+        .find(&command)
+        .ok_or_else(|| CommandError::CommandNotAllowed(command.clone()))?;
+
+    let full_args = build_allowed_args(allowed, args)?;
+
     let output = Command::new(&command)
-        .args(&args)
+        .args(&full_args)
         .output()
-        .map_err(|e| format!("Failed to execute command '{}': {}", command, e))?;
+        .map_err(|e| CommandError::Io(format!("failed to execute command '{}': {}", command, e)))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
@@ -76,16 +451,123 @@ fn run_command(command: String, args: Vec<String>) -> Result<serde_json::Value,
     }))
 }
 
+#[tauri::command]
+fn list_allowed_commands(app: tauri::AppHandle) -> Vec<AllowedCommand> {
+    app.state::<CommandScope>().allowed.clone()
+}
+
+#[tauri::command]
+fn set_auto_lock_timeout(app: tauri::AppHandle, minutes: u64) -> Result<(), String> {
+    let auto_lock = app.state::<AutoLockState>();
+    *auto_lock.timeout_minutes.lock().unwrap() = minutes;
+    Ok(())
+}
+
+#[tauri::command]
+fn lock_now(app: tauri::AppHandle) -> Result<(), String> {
+    lock_main_window(&app);
+    Ok(())
+}
+
+#[tauri::command]
+async fn check_for_update(app: tauri::AppHandle) -> Result<Option<UpdateInfo>, String> {
+    check_for_update_internal(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn install_update(app: tauri::AppHandle) -> Result<(), String> {
+    install_update_internal(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_global_shortcut(app: tauri::AppHandle, action: String, accelerator: String) -> Result<(), String> {
+    let new_shortcut = Shortcut::try_from(accelerator.as_str()).map_err(|e| e.to_string())?;
+
+    let registry_state = app.state::<Mutex<ShortcutRegistry>>();
+    let mut registry = registry_state.lock().unwrap();
+
+    // Register the new accelerator first: if it's already taken elsewhere
+    // this fails and we leave the existing binding untouched, rather than
+    // unregistering it and then failing to put a replacement in its place.
+    app.global_shortcut()
+        .register(new_shortcut.clone())
+        .map_err(|e| e.to_string())?;
+
+    if let Some((_, old_shortcut)) = registry.bindings.get(&action) {
+        if old_shortcut != &new_shortcut {
+            let _ = app.global_shortcut().unregister(old_shortcut.clone());
+        }
+    }
+
+    registry.bindings.insert(action, (accelerator, new_shortcut));
+
+    let persisted: HashMap<String, String> = registry
+        .bindings
+        .iter()
+        .map(|(action, (accelerator, _))| (action.clone(), accelerator.clone()))
+        .collect();
+    drop(registry);
+
+    save_shortcut_bindings(&app, &persisted).map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            // Another launch attempt was made while we're already running;
+            // surface the existing window instead of starting a second instance.
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_window_state::Builder::default().build())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_sql::Builder::default().build())
-        .invoke_handler(tauri::generate_handler![greet, get_background_task_status, trigger_background_task, send_notification, notify_due_action_item, clear_due_item_notifications, run_command])
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        handle_global_shortcut(app, shortcut);
+                    }
+                })
+                .build(),
+        )
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .invoke_handler(tauri::generate_handler![greet, get_background_task_status, trigger_background_task, send_notification, notify_due_action_item, clear_due_item_notifications, report_due_item_count, run_command, list_allowed_commands, set_global_shortcut, set_auto_lock_timeout, lock_now, check_for_update, install_update])
         .setup(|app| {
+            app.manage(load_command_scope(app.handle()));
+
+            // Register the configured (or default) global shortcuts
+            let mut shortcut_registry = ShortcutRegistry { bindings: HashMap::new() };
+            for (action, accelerator) in load_shortcut_bindings(app.handle()) {
+                match Shortcut::try_from(accelerator.as_str()) {
+                    Ok(shortcut) => match app.global_shortcut().register(shortcut.clone()) {
+                        Ok(_) => {
+                            shortcut_registry.bindings.insert(action, (accelerator, shortcut));
+                        }
+                        Err(e) => eprintln!("Failed to register shortcut '{}' for '{}': {}", accelerator, action, e),
+                    },
+                    Err(e) => eprintln!("Invalid accelerator '{}' for '{}': {}", accelerator, action, e),
+                }
+            }
+            app.manage(Mutex::new(shortcut_registry));
+
+            // Track activity for the idle auto-lock
+            app.manage(AutoLockState {
+                last_activity: Mutex::new(SystemTime::now()),
+                timeout_minutes: Mutex::new(DEFAULT_AUTO_LOCK_MINUTES),
+            });
+            {
+                let app_handle = app.handle().clone();
+                app.listen("user-activity", move |_event| {
+                    record_activity(&app_handle);
+                });
+            }
+
             // Create proper menu with standard shortcuts for macOS
             #[cfg(target_os = "macos")]
             {
@@ -121,29 +603,17 @@ pub fn run() {
                 app.set_menu(menu)?;
             }
 
-            // Create tray menu items
-            let show_item = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let hide_item = MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-
-            // Create tray menu
-            let tray_menu = Menu::with_items(app, &[&show_item, &hide_item, &quit_item])?;            // Create tray icon
-            let _tray = TrayIconBuilder::new()
+            // Create the initial tray menu (window starts visible, so "Hide" is offered)
+            let tray_menu = build_tray_menu(app, true, false)?;
+            let tray = TrayIconBuilder::new()
                 .menu(&tray_menu)
+                .tooltip(tray_tooltip(0))
                 .icon(app.default_window_icon().unwrap().clone())
                 .show_menu_on_left_click(false)
                 .on_tray_icon_event(|tray, event| {
                     match event {
                         TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } => {
-                            let app = tray.app_handle();
-                            if let Some(window) = app.get_webview_window("main") {
-                                if window.is_visible().unwrap_or(false) {
-                                    let _ = window.hide();
-                                } else {
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                }
-                            }
+                            toggle_main_window(tray.app_handle());
                         }
                         _ => {}
                     }
@@ -155,11 +625,21 @@ pub fn run() {
                                 let _ = window.show();
                                 let _ = window.set_focus();
                             }
+                            let _ = refresh_tray(app, Some(true), None, None);
                         }
                         "hide" => {
                             if let Some(window) = app.get_webview_window("main") {
                                 let _ = window.hide();
                             }
+                            let _ = refresh_tray(app, Some(false), None, None);
+                        }
+                        "install_update" => {
+                            let app_handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = install_update_internal(&app_handle).await {
+                                    eprintln!("Failed to install update: {}", e);
+                                }
+                            });
                         }
                         "quit" => {
                             app.exit(0);
@@ -169,6 +649,19 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            app.manage(Mutex::new(TrayState { tray, visible: true, due_count: 0, update_available: false }));
+
+            // Check for an update once at startup
+            app.manage(UpdateState { pending: Mutex::new(None) });
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = check_for_update_internal(&app_handle).await {
+                        eprintln!("Startup update check failed: {}", e);
+                    }
+                });
+            }
+
             // Set activation policy for macOS to hide dock icon
             #[cfg(target_os = "macos")]
             {
@@ -177,22 +670,44 @@ pub fn run() {
 
             // Start background task
             let app_handle = app.handle().clone();
+            app.manage(Mutex::new(HashSet::<(String, String)>::new()));
             tauri::async_runtime::spawn(async move {
+                // A failed open here shouldn't take auto-lock and update checks
+                // down with it, so the pool is re-attempted on a later tick
+                // instead of the task bailing out for good.
+                let mut db_pool = match open_db_pool(&app_handle).await {
+                    Ok(pool) => Some(pool),
+                    Err(e) => {
+                        eprintln!("Failed to open database for due-item checks: {}", e);
+                        None
+                    }
+                };
                 let mut interval = tokio::time::interval(Duration::from_secs(60)); // Run every 60 seconds for testing
+                let mut tick: u32 = 0;
 
                 loop {
                     interval.tick().await;
+                    tick += 1;
 
                     // Example background task - you can replace this with your actual logic
                     println!("Background task running...");
 
-                    // Check for due action items by requesting the frontend to do the check
-                    match app_handle.emit("check-due-action-items", ()) {
-                        Ok(_) => {
-                            println!("Requested due action items check from frontend");
+                    if db_pool.is_none() {
+                        match open_db_pool(&app_handle).await {
+                            Ok(pool) => db_pool = Some(pool),
+                            Err(e) => eprintln!("Retrying database open for due-item checks failed: {}", e),
                         }
-                        Err(e) => {
-                            eprintln!("Failed to request due action items check: {}", e);
+                    }
+                    if let Some(pool) = &db_pool {
+                        let notified = app_handle.state::<Mutex<HashSet<(String, String)>>>();
+                        check_due_action_items(&app_handle, pool, &notified).await;
+                    }
+
+                    check_auto_lock(&app_handle, &app_handle.state::<AutoLockState>());
+
+                    if tick % UPDATE_CHECK_TICK_INTERVAL == 0 {
+                        if let Err(e) = check_for_update_internal(&app_handle).await {
+                            eprintln!("Update check failed: {}", e);
                         }
                     }
 
@@ -208,11 +723,18 @@ pub fn run() {
             if let Some(window) = app.get_webview_window("main") {
                 let app_handle = app.handle().clone();
                 window.on_window_event(move |event| {
-                    if let WindowEvent::CloseRequested { api, .. } = event {
-                        api.prevent_close();
-                        if let Some(window) = app_handle.get_webview_window("main") {
-                            let _ = window.hide();
+                    match event {
+                        WindowEvent::CloseRequested { api, .. } => {
+                            api.prevent_close();
+                            if let Some(window) = app_handle.get_webview_window("main") {
+                                let _ = window.hide();
+                            }
+                            let _ = refresh_tray(&app_handle, Some(false), None, None);
                         }
+                        WindowEvent::Focused(true) => {
+                            record_activity(&app_handle);
+                        }
+                        _ => {}
                     }
                 });
             }
@@ -222,3 +744,58 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(arg_prefixes: Vec<&str>, fixed_args: Vec<&str>) -> AllowedCommand {
+        AllowedCommand {
+            name: "git".to_string(),
+            arg_prefixes: arg_prefixes.into_iter().map(String::from).collect(),
+            fixed_args: fixed_args.into_iter().map(String::from).collect(),
+        }
+    }
+
+    #[test]
+    fn find_matches_by_name() {
+        let scope = CommandScope { allowed: vec![allowed(vec!["--"], vec![])] };
+        assert!(scope.find("git").is_some());
+        assert!(scope.find("rm").is_none());
+    }
+
+    #[test]
+    fn build_allowed_args_accepts_matching_prefix() {
+        let cmd = allowed(vec!["--"], vec![]);
+        let args = build_allowed_args(&cmd, vec!["--version".to_string()]).unwrap();
+        assert_eq!(args, vec!["--version".to_string()]);
+    }
+
+    #[test]
+    fn build_allowed_args_rejects_non_matching_prefix() {
+        let cmd = allowed(vec!["--"], vec![]);
+        let err = build_allowed_args(&cmd, vec!["push".to_string()]).unwrap_err();
+        assert!(matches!(err, CommandError::ArgumentNotAllowed(arg) if arg == "push"));
+    }
+
+    #[test]
+    fn empty_prefix_list_denies_all_args() {
+        let cmd = allowed(vec![], vec![]);
+        let err = build_allowed_args(&cmd, vec!["--version".to_string()]).unwrap_err();
+        assert!(matches!(err, CommandError::ArgumentNotAllowed(_)));
+    }
+
+    #[test]
+    fn empty_prefix_list_allows_no_args() {
+        let cmd = allowed(vec![], vec![]);
+        let args = build_allowed_args(&cmd, vec![]).unwrap();
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn fixed_args_are_prepended_before_provided_args() {
+        let cmd = allowed(vec!["--"], vec!["status"]);
+        let args = build_allowed_args(&cmd, vec!["--short".to_string()]).unwrap();
+        assert_eq!(args, vec!["status".to_string(), "--short".to_string()]);
+    }
+}